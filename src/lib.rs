@@ -6,7 +6,16 @@ use diffx_core::{
 };
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
+use rayon::prelude::*;
 use regex::Regex;
+use serde::Serialize;
+use std::cell::Cell;
+
+thread_local! {
+    /// Guards against `compareValues` calling back into `diff()`/`createPatch()`/
+    /// `diffBatch()` from within the comparison it is itself servicing.
+    static IN_COMPARE_VALUES: Cell<bool> = const { Cell::new(false) };
+}
 
 #[napi(object)]
 pub struct JsDiffOptions {
@@ -39,6 +48,32 @@ pub struct JsDiffOptions {
     pub quiet_mode: Option<bool>,
 }
 
+#[napi(object)]
+pub struct JsDiffPair {
+    /// The old value of this pair
+    pub old: serde_json::Value,
+
+    /// The new value of this pair
+    #[napi(js_name = "new")]
+    pub new_value: serde_json::Value,
+}
+
+#[napi(object)]
+pub struct JsDiffBatchResult {
+    /// Diff results for this pair, present when diffing it succeeded
+    pub results: Option<Vec<JsDiffResult>>,
+
+    /// Error message for this pair, present when diffing it failed
+    pub error: Option<String>,
+}
+
+#[napi(object)]
+pub struct JsApplyPatchOptions {
+    /// Maximum number of mismatched `old_value` checks to tolerate before
+    /// rejecting the patch (mirrors jsdiff's `fuzzFactor`)
+    pub fuzz_factor: Option<u32>,
+}
+
 #[napi(object)]
 pub struct JsDiffResult {
     /// Type of difference ('Added', 'Removed', 'Modified', 'TypeChanged')
@@ -81,11 +116,18 @@ pub struct JsDiffResult {
 /// const result = diff(old, new);
 /// console.log(result); // [{ type: 'Modified', path: 'b', oldValue: 2, newValue: 3 }]
 /// ```
+///
+/// Pass `compareValues` as a final, separate `(path, oldValue, newValue) => boolean`
+/// callback to suppress leaf pairs it reports as equal from the result set. It is
+/// called directly on the calling (JS) thread, so it must not call back into
+/// `diff()`/`createPatch()`/`diffBatch()`.
 #[napi]
 pub fn diff(
+    env: Env,
     old: serde_json::Value,
     #[napi(ts_arg_type = "any")] new_value: serde_json::Value,
     options: Option<JsDiffOptions>,
+    compare_values: Option<JsFunction>,
 ) -> Result<Vec<JsDiffResult>> {
     // Convert options
     let rust_options = options.map(build_diff_options).transpose()?;
@@ -100,7 +142,489 @@ pub fn diff(
         .map(convert_diff_result)
         .collect::<Result<Vec<_>>>()?;
 
-    Ok(js_results)
+    match compare_values {
+        Some(callback) => filter_with_compare_values(&env, js_results, &callback),
+        None => Ok(js_results),
+    }
+}
+
+/// Diff many pairs in parallel
+///
+/// Fans `pairs` out across a rayon thread pool instead of paying per-call napi
+/// overhead for each pair individually, which matters for CI jobs comparing
+/// hundreds of config files. Results preserve input order. A malformed pair
+/// does not abort the batch: its slot carries an `error` instead of `results`.
+///
+/// The parallel stage only runs `core_diff`, which is pure Rust and safe to
+/// share across the rayon pool. `compareValues` is a JS callback and can only
+/// be invoked from the JS thread, so it is applied afterwards, once every pair
+/// has been diffed, rather than from inside the rayon closures.
+///
+/// # Arguments
+///
+/// * `pairs` - Array of `{ old, new }` value pairs to diff
+/// * `options` - Optional configuration object, applied to every pair
+/// * `compareValues` - Optional `(path, oldValue, newValue) => boolean` equality callback
+///
+/// # Returns
+///
+/// Array of per-pair results, one `{ results, error }` entry per input pair
+#[napi]
+pub fn diff_batch(
+    env: Env,
+    pairs: Vec<JsDiffPair>,
+    options: Option<JsDiffOptions>,
+    compare_values: Option<JsFunction>,
+) -> Result<Vec<JsDiffBatchResult>> {
+    let rust_options = options.map(build_diff_options).transpose()?;
+
+    let raw_results: Vec<std::result::Result<Vec<DiffResult>, String>> = pairs
+        .par_iter()
+        .map(|pair| {
+            core_diff(&pair.old, &pair.new_value, rust_options.as_ref())
+                .map_err(|e| format!("Diff error: {e}"))
+        })
+        .collect();
+
+    Ok(raw_results
+        .into_iter()
+        .map(|pair_result| {
+            let to_js_results = |results: Vec<DiffResult>| -> Result<Vec<JsDiffResult>> {
+                let js_results = results
+                    .into_iter()
+                    .map(convert_diff_result)
+                    .collect::<Result<Vec<_>>>()?;
+                match &compare_values {
+                    Some(callback) => filter_with_compare_values(&env, js_results, callback),
+                    None => Ok(js_results),
+                }
+            };
+
+            match pair_result.and_then(|results| to_js_results(results).map_err(|e| e.to_string())) {
+                Ok(filtered) => JsDiffBatchResult {
+                    results: Some(filtered),
+                    error: None,
+                },
+                Err(message) => JsDiffBatchResult {
+                    results: None,
+                    error: Some(message),
+                },
+            }
+        })
+        .collect())
+}
+
+/// Create a patch (diff result list) that can later be replayed with `applyPatch`
+///
+/// This is `diff()` under a name that reflects the round-trip workflow: the
+/// returned list is the same `JsDiffResult[]`, but it is meant to be stored
+/// or transmitted and later applied with [`apply_patch`].
+///
+/// # Arguments
+///
+/// * `old` - The old value (JavaScript object, array, or primitive)
+/// * `new` - The new value (JavaScript object, array, or primitive)
+/// * `options` - Optional configuration object
+///
+/// # Returns
+///
+/// Array of difference objects, suitable for `applyPatch`
+#[napi]
+pub fn create_patch(
+    env: Env,
+    old: serde_json::Value,
+    #[napi(ts_arg_type = "any")] new_value: serde_json::Value,
+    options: Option<JsDiffOptions>,
+    compare_values: Option<JsFunction>,
+) -> Result<Vec<JsDiffResult>> {
+    diff(env, old, new_value, options, compare_values)
+}
+
+/// Apply a patch produced by `createPatch`/`diff` onto a value
+///
+/// Walks each `JsDiffResult` in `patch`, parsing its `path` into object-key
+/// and array-index segments, and mutates a clone of `old` accordingly:
+/// `Added` inserts `new_value` (creating intermediate objects/arrays as
+/// needed), `Removed` deletes the element, and `Modified`/`TypeChanged`
+/// overwrite the leaf with `new_value`.
+///
+/// Entries are applied in ascending path order (numeric for array indices)
+/// rather than patch order, and a per-array net offset is tracked as
+/// `Added`/`Removed` entries are applied. This re-bases every later index
+/// into the same array so that, e.g., removing elements `[2]`, `[3]`, `[4]`
+/// of a 5-element array still finds `c`, `d`, `e` at those positions rather
+/// than reading past elements shifted down by earlier removals.
+///
+/// # Arguments
+///
+/// * `old` - The original value the patch was created against
+/// * `patch` - Diff results produced by `createPatch` or `diff`
+/// * `options` - Optional `fuzzFactor` tolerance for stale `old_value` checks
+///
+/// # Returns
+///
+/// The reconstructed value, or an error if the patch no longer applies
+#[napi]
+pub fn apply_patch(
+    old: serde_json::Value,
+    patch: Vec<JsDiffResult>,
+    options: Option<JsApplyPatchOptions>,
+) -> Result<serde_json::Value> {
+    let fuzz_factor = options.and_then(|o| o.fuzz_factor).unwrap_or(0);
+    let mut mismatches = 0u32;
+    let mut result = old;
+    let mut offsets: std::collections::HashMap<String, i64> = std::collections::HashMap::new();
+
+    let mut entries: Vec<(Vec<PathSegment>, JsDiffResult)> = patch
+        .into_iter()
+        .map(|entry| parse_path_segments(&entry.path).map(|segments| (segments, entry)))
+        .collect::<Result<Vec<_>>>()?;
+
+    // Ascending order (numeric for indices) so each array is visited low-to-high,
+    // letting `offsets` accumulate correctly as elements are removed/inserted.
+    entries.sort_by(|(a, _), (b, _)| compare_segments(a, b));
+
+    for (segments, entry) in entries {
+        let rebased = rebase_segments(&segments, &offsets);
+        let is_array_element = matches!(segments.last(), Some(PathSegment::Index(_)));
+
+        match entry.diff_type.as_str() {
+            "Added" => {
+                let value = entry.new_value.ok_or_else(|| {
+                    Error::new(Status::InvalidArg, "Added result must have new_value")
+                })?;
+                if is_array_element {
+                    insert_at_path(&mut result, &rebased, value)?;
+                    bump_offset(&mut offsets, &rebased, 1);
+                } else {
+                    set_at_path(&mut result, &rebased, value)?;
+                }
+            }
+            "Removed" => {
+                let expected = entry
+                    .value
+                    .ok_or_else(|| Error::new(Status::InvalidArg, "Removed result must have value"))?;
+                check_fuzz(&result, &rebased, &expected, fuzz_factor, &mut mismatches)?;
+                remove_at_path(&mut result, &rebased)?;
+                if is_array_element {
+                    bump_offset(&mut offsets, &rebased, -1);
+                }
+            }
+            "Modified" | "TypeChanged" => {
+                let expected = entry.old_value.ok_or_else(|| {
+                    Error::new(
+                        Status::InvalidArg,
+                        format!("{} result must have old_value", entry.diff_type),
+                    )
+                })?;
+                let new_value = entry.new_value.ok_or_else(|| {
+                    Error::new(
+                        Status::InvalidArg,
+                        format!("{} result must have new_value", entry.diff_type),
+                    )
+                })?;
+                check_fuzz(&result, &rebased, &expected, fuzz_factor, &mut mismatches)?;
+                set_at_path(&mut result, &rebased, new_value)?;
+            }
+            other => {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    format!("Invalid diff result type: {other}"),
+                ))
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Order path segments ascending, comparing array indices numerically so that
+/// sorting a patch by path visits each array low-to-high
+fn compare_segments(a: &[PathSegment], b: &[PathSegment]) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+    for (sa, sb) in a.iter().zip(b.iter()) {
+        let ord = match (sa, sb) {
+            (PathSegment::Key(ka), PathSegment::Key(kb)) => ka.cmp(kb),
+            (PathSegment::Index(ia), PathSegment::Index(ib)) => ia.cmp(ib),
+            (PathSegment::Key(_), PathSegment::Index(_)) => Ordering::Less,
+            (PathSegment::Index(_), PathSegment::Key(_)) => Ordering::Greater,
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+/// Render segments back into a stable string key, used to key the per-array offset map
+fn segments_key(segments: &[PathSegment]) -> String {
+    let mut key = String::new();
+    for segment in segments {
+        match segment {
+            PathSegment::Key(k) => {
+                key.push('.');
+                key.push_str(k);
+            }
+            PathSegment::Index(i) => {
+                key.push('[');
+                key.push_str(&i.to_string());
+                key.push(']');
+            }
+        }
+    }
+    key
+}
+
+/// Re-base every index segment in `segments` by the net offset accumulated so far
+/// for the array it addresses, so later entries land on the element the original
+/// diff actually meant rather than one shifted by earlier removals/insertions
+fn rebase_segments(
+    segments: &[PathSegment],
+    offsets: &std::collections::HashMap<String, i64>,
+) -> Vec<PathSegment> {
+    let mut rebased: Vec<PathSegment> = Vec::with_capacity(segments.len());
+    for segment in segments {
+        match segment {
+            PathSegment::Key(k) => rebased.push(PathSegment::Key(k.clone())),
+            PathSegment::Index(i) => {
+                let offset = offsets.get(&segments_key(&rebased)).copied().unwrap_or(0);
+                let actual = (*i as i64 + offset).max(0) as usize;
+                rebased.push(PathSegment::Index(actual));
+            }
+        }
+    }
+    rebased
+}
+
+/// Record that the array addressed by `rebased` (minus its last, index segment)
+/// grew (`delta = 1`) or shrank (`delta = -1`) by one element
+fn bump_offset(
+    offsets: &mut std::collections::HashMap<String, i64>,
+    rebased: &[PathSegment],
+    delta: i64,
+) {
+    let prefix = &rebased[..rebased.len() - 1];
+    *offsets.entry(segments_key(prefix)).or_insert(0) += delta;
+}
+
+/// A single segment of a parsed diff `path` string
+#[derive(Debug, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Parse a diff `path` (e.g. `"a.b[0].c"`) into object-key and array-index segments
+fn parse_path_segments(path: &str) -> Result<Vec<PathSegment>> {
+    let mut segments = Vec::new();
+    for part in path.split('.') {
+        if part.is_empty() {
+            continue;
+        }
+        let mut rest = part;
+        if let Some(bracket_pos) = rest.find('[') {
+            let key = &rest[..bracket_pos];
+            if !key.is_empty() {
+                segments.push(PathSegment::Key(key.to_string()));
+            }
+            rest = &rest[bracket_pos..];
+            while let Some(stripped) = rest.strip_prefix('[') {
+                let end = stripped.find(']').ok_or_else(|| {
+                    Error::new(Status::InvalidArg, format!("Malformed path: {path}"))
+                })?;
+                let index: usize = stripped[..end].parse().map_err(|_| {
+                    Error::new(Status::InvalidArg, format!("Malformed path: {path}"))
+                })?;
+                segments.push(PathSegment::Index(index));
+                rest = &stripped[end + 1..];
+            }
+        } else {
+            segments.push(PathSegment::Key(rest.to_string()));
+        }
+    }
+    Ok(segments)
+}
+
+/// Walk `parents` within `value`, creating intermediate objects/arrays as needed,
+/// and return a mutable reference to the container the last segment addresses
+fn navigate_to_parent_mut<'v>(
+    value: &'v mut serde_json::Value,
+    parents: &[PathSegment],
+) -> &'v mut serde_json::Value {
+    let mut current = value;
+    for segment in parents {
+        current = match segment {
+            PathSegment::Key(key) => {
+                if !current.is_object() {
+                    *current = serde_json::Value::Object(serde_json::Map::new());
+                }
+                current
+                    .as_object_mut()
+                    .unwrap()
+                    .entry(key.clone())
+                    .or_insert(serde_json::Value::Null)
+            }
+            PathSegment::Index(index) => {
+                if !current.is_array() {
+                    *current = serde_json::Value::Array(Vec::new());
+                }
+                let array = current.as_array_mut().unwrap();
+                while array.len() <= *index {
+                    array.push(serde_json::Value::Null);
+                }
+                &mut array[*index]
+            }
+        };
+    }
+    current
+}
+
+/// Navigate to `segments` within `value`, creating intermediate objects/arrays as needed,
+/// and overwrite the leaf with `new_value`. For an array-index leaf this replaces the
+/// element in place, which is correct for `Modified`/`TypeChanged` but NOT for `Added`
+/// (use `insert_at_path` there, or this silently overwrites the element that would have
+/// shifted into that slot)
+fn set_at_path(value: &mut serde_json::Value, segments: &[PathSegment], new_value: serde_json::Value) -> Result<()> {
+    let Some((last, parents)) = segments.split_last() else {
+        *value = new_value;
+        return Ok(());
+    };
+
+    let current = navigate_to_parent_mut(value, parents);
+
+    match last {
+        PathSegment::Key(key) => {
+            if !current.is_object() {
+                *current = serde_json::Value::Object(serde_json::Map::new());
+            }
+            current
+                .as_object_mut()
+                .unwrap()
+                .insert(key.clone(), new_value);
+        }
+        PathSegment::Index(index) => {
+            if !current.is_array() {
+                *current = serde_json::Value::Array(Vec::new());
+            }
+            let array = current.as_array_mut().unwrap();
+            while array.len() <= *index {
+                array.push(serde_json::Value::Null);
+            }
+            array[*index] = new_value;
+        }
+    }
+
+    Ok(())
+}
+
+/// Navigate to `segments` within `value`, creating intermediate objects/arrays as needed.
+/// For an array-index leaf, inserts `new_value` ahead of whatever currently sits at that
+/// index (shifting it and everything after it right) instead of overwriting it — the
+/// semantics an `Added` patch entry needs. For an object-key leaf this is identical to
+/// `set_at_path`, since inserting and overwriting a map entry are the same operation.
+fn insert_at_path(value: &mut serde_json::Value, segments: &[PathSegment], new_value: serde_json::Value) -> Result<()> {
+    let Some((last, parents)) = segments.split_last() else {
+        *value = new_value;
+        return Ok(());
+    };
+
+    let current = navigate_to_parent_mut(value, parents);
+
+    match last {
+        PathSegment::Key(key) => {
+            if !current.is_object() {
+                *current = serde_json::Value::Object(serde_json::Map::new());
+            }
+            current
+                .as_object_mut()
+                .unwrap()
+                .insert(key.clone(), new_value);
+        }
+        PathSegment::Index(index) => {
+            if !current.is_array() {
+                *current = serde_json::Value::Array(Vec::new());
+            }
+            let array = current.as_array_mut().unwrap();
+            let index = (*index).min(array.len());
+            array.insert(index, new_value);
+        }
+    }
+
+    Ok(())
+}
+
+/// Navigate to `segments` within `value` and remove the leaf element
+fn remove_at_path(value: &mut serde_json::Value, segments: &[PathSegment]) -> Result<()> {
+    let Some((last, parents)) = segments.split_last() else {
+        *value = serde_json::Value::Null;
+        return Ok(());
+    };
+
+    let mut current = value;
+    for segment in parents {
+        current = match segment {
+            PathSegment::Key(key) => current
+                .get_mut(key)
+                .ok_or_else(|| Error::new(Status::InvalidArg, "Patch path not found"))?,
+            PathSegment::Index(index) => current
+                .get_mut(*index)
+                .ok_or_else(|| Error::new(Status::InvalidArg, "Patch path not found"))?,
+        };
+    }
+
+    match last {
+        PathSegment::Key(key) => {
+            current
+                .as_object_mut()
+                .and_then(|obj| obj.remove(key))
+                .ok_or_else(|| Error::new(Status::InvalidArg, "Patch path not found"))?;
+        }
+        PathSegment::Index(index) => {
+            let array = current
+                .as_array_mut()
+                .ok_or_else(|| Error::new(Status::InvalidArg, "Patch path not found"))?;
+            if *index >= array.len() {
+                return Err(Error::new(Status::InvalidArg, "Patch path not found"));
+            }
+            array.remove(*index);
+        }
+    }
+
+    Ok(())
+}
+
+/// Read the value at `segments` within `value`, if any
+fn get_at_path<'a>(value: &'a serde_json::Value, segments: &[PathSegment]) -> Option<&'a serde_json::Value> {
+    let mut current = value;
+    for segment in segments {
+        current = match segment {
+            PathSegment::Key(key) => current.get(key)?,
+            PathSegment::Index(index) => current.get(*index)?,
+        };
+    }
+    Some(current)
+}
+
+/// Compare the value currently at `segments` against `expected`, tolerating up to
+/// `fuzz_factor` mismatches before failing the patch application
+fn check_fuzz(
+    value: &serde_json::Value,
+    segments: &[PathSegment],
+    expected: &serde_json::Value,
+    fuzz_factor: u32,
+    mismatches: &mut u32,
+) -> Result<()> {
+    let actual = get_at_path(value, segments);
+    if actual != Some(expected) {
+        *mismatches += 1;
+        if *mismatches > fuzz_factor {
+            return Err(Error::new(
+                Status::GenericFailure,
+                "Patch does not apply: old_value mismatch exceeds fuzzFactor",
+            ));
+        }
+    }
+    Ok(())
 }
 
 /// Parse JSON string to JavaScript object
@@ -118,6 +642,134 @@ pub fn parse_json(content: String) -> Result<serde_json::Value> {
         .map_err(|e| Error::new(Status::InvalidArg, format!("JSON parse error: {e}")))
 }
 
+/// Parse JSONC (JSON with `//`/`/* */` comments and trailing commas) to a JavaScript object
+///
+/// Many real-world config files (tsconfig.json, VS Code settings) are JSONC
+/// rather than strict JSON. This strips comments and trailing commas with a
+/// small state machine that respects string literals and escape sequences,
+/// then hands the result to `core_parse_json`, so the returned value and
+/// downstream `diff()` behavior stay identical to plain JSON parsing.
+///
+/// # Arguments
+///
+/// * `content` - JSONC string to parse
+///
+/// # Returns
+///
+/// Parsed JavaScript object
+#[napi]
+pub fn parse_jsonc(content: String) -> Result<serde_json::Value> {
+    let stripped = strip_jsonc(&content);
+    core_parse_json(&stripped)
+        .map_err(|e| Error::new(Status::InvalidArg, format!("JSONC parse error: {e}")))
+}
+
+/// Strip `//` and `/* */` comments and trailing commas from `content`, leaving
+/// string literals (and their escape sequences) untouched
+fn strip_jsonc(content: &str) -> String {
+    #[derive(PartialEq)]
+    enum State {
+        Normal,
+        InString,
+        Escape,
+        LineComment,
+        BlockComment,
+    }
+
+    let chars: Vec<char> = content.chars().collect();
+    let mut out = String::with_capacity(content.len());
+    let mut state = State::Normal;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        match state {
+            State::Normal => {
+                if c == '"' {
+                    state = State::InString;
+                    out.push(c);
+                } else if c == '/' && next == Some('/') {
+                    state = State::LineComment;
+                    i += 2;
+                    continue;
+                } else if c == '/' && next == Some('*') {
+                    state = State::BlockComment;
+                    i += 2;
+                    continue;
+                } else if c == ',' {
+                    // Drop the comma if only whitespace/comments precede a closing
+                    // `}`/`]`, making it a trailing comma.
+                    if next_significant_is_closer(&chars, i + 1) {
+                        i += 1;
+                        continue;
+                    }
+                    out.push(c);
+                } else {
+                    out.push(c);
+                }
+            }
+            State::InString => {
+                if c == '\\' {
+                    state = State::Escape;
+                } else if c == '"' {
+                    state = State::Normal;
+                }
+                out.push(c);
+            }
+            State::Escape => {
+                out.push(c);
+                state = State::InString;
+            }
+            State::LineComment => {
+                if c == '\n' {
+                    state = State::Normal;
+                    out.push(c);
+                }
+            }
+            State::BlockComment => {
+                if c == '*' && next == Some('/') {
+                    state = State::Normal;
+                    i += 2;
+                    continue;
+                }
+            }
+        }
+
+        i += 1;
+    }
+
+    out
+}
+
+/// Look ahead from `chars[start..]`, skipping whitespace and comments, and report
+/// whether the next significant character is a `}` or `]` (i.e. the comma before
+/// it is a trailing comma that should be dropped)
+fn next_significant_is_closer(chars: &[char], start: usize) -> bool {
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            c if c.is_whitespace() => i += 1,
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            '}' | ']' => return true,
+            _ => return false,
+        }
+    }
+    false
+}
+
 /// Parse CSV string to JavaScript array of objects
 ///
 /// # Arguments
@@ -198,28 +850,228 @@ pub fn parse_xml(content: String) -> Result<serde_json::Value> {
 /// # Arguments
 ///
 /// * `results` - Array of diff results
-/// * `format` - Output format ("diffx", "json", "yaml")
+/// * `format` - Output format ("diffx", "json", "yaml", "unified", "pretty-json")
+/// * `indent` - Indentation width for "unified"/"pretty-json" (default 2)
 ///
 /// # Returns
 ///
 /// Formatted string output
 #[napi]
-pub fn format_output(results: Vec<JsDiffResult>, format: String) -> Result<String> {
-    // Convert JS results back to Rust DiffResult
-    let rust_results = results
-        .into_iter()
-        .map(convert_js_diff_result)
-        .collect::<Result<Vec<_>>>()?;
+pub fn format_output(
+    results: Vec<JsDiffResult>,
+    format: String,
+    indent: Option<u32>,
+) -> Result<String> {
+    match format.as_str() {
+        "unified" => Ok(format_unified(&results, indent.unwrap_or(2) as usize)),
+        "pretty-json" => format_pretty_json(&results, indent.unwrap_or(2) as usize),
+        _ => {
+            // Convert JS results back to Rust DiffResult
+            let rust_results = results
+                .into_iter()
+                .map(convert_js_diff_result)
+                .collect::<Result<Vec<_>>>()?;
 
-    let output_format = OutputFormat::parse_format(&format)
-        .map_err(|e| Error::new(Status::InvalidArg, format!("Invalid format: {e}")))?;
+            let output_format = OutputFormat::parse_format(&format)
+                .map_err(|e| Error::new(Status::InvalidArg, format!("Invalid format: {e}")))?;
 
-    core_format_output(&rust_results, output_format)
-        .map_err(|e| Error::new(Status::GenericFailure, format!("Format error: {e}")))
+            core_format_output(&rust_results, output_format)
+                .map_err(|e| Error::new(Status::GenericFailure, format!("Format error: {e}")))
+        }
+    }
 }
 
 // Helper functions
 
+/// Render diff results as a conventional unified (`+`/`-`) patch, grouped by
+/// top-level key, with removed lines marked `-`, added `+`, and modified as a
+/// `-`/`+` pair
+fn format_unified(results: &[JsDiffResult], indent: usize) -> String {
+    let mut sorted: Vec<&JsDiffResult> = results.iter().collect();
+    sorted.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let pad = " ".repeat(indent);
+    let mut out = String::new();
+    let mut current_group: Option<String> = None;
+
+    for result in sorted {
+        let group = top_level_key(&result.path);
+        if current_group.as_deref() != Some(group) {
+            if current_group.is_some() {
+                out.push('\n');
+            }
+            out.push_str(&format!("@@ {group} @@\n"));
+            current_group = Some(group.to_string());
+        }
+
+        match result.diff_type.as_str() {
+            "Added" => {
+                out.push_str(&format!(
+                    "{pad}+ {}: {}\n",
+                    result.path,
+                    format_value(result.new_value.as_ref())
+                ));
+            }
+            "Removed" => {
+                out.push_str(&format!(
+                    "{pad}- {}: {}\n",
+                    result.path,
+                    format_value(result.value.as_ref())
+                ));
+            }
+            "Modified" | "TypeChanged" => {
+                out.push_str(&format!(
+                    "{pad}- {}: {}\n",
+                    result.path,
+                    format_value(result.old_value.as_ref())
+                ));
+                out.push_str(&format!(
+                    "{pad}+ {}: {}\n",
+                    result.path,
+                    format_value(result.new_value.as_ref())
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    out
+}
+
+/// The top-level key of a diff `path` (e.g. `"a"` for both `"a.b"` and `"a[0]"`)
+fn top_level_key(path: &str) -> &str {
+    let end = path.find(['.', '[']).unwrap_or(path.len());
+    &path[..end]
+}
+
+fn format_value(value: Option<&serde_json::Value>) -> String {
+    value
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "null".to_string())
+}
+
+/// Render diff results as indented, stable-key-ordered JSON
+fn format_pretty_json(results: &[JsDiffResult], indent: usize) -> Result<String> {
+    let json_results: Vec<serde_json::Value> = results.iter().map(diff_result_to_json).collect();
+    let sorted = sort_json_keys(serde_json::Value::Array(json_results));
+
+    let indent_bytes = " ".repeat(indent);
+    let mut buf = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent_bytes.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut buf, formatter);
+    sorted
+        .serialize(&mut serializer)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Pretty JSON format error: {e}")))?;
+
+    String::from_utf8(buf)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Pretty JSON format error: {e}")))
+}
+
+fn diff_result_to_json(result: &JsDiffResult) -> serde_json::Value {
+    serde_json::json!({
+        "type": result.diff_type,
+        "path": result.path,
+        "oldValue": result.old_value,
+        "newValue": result.new_value,
+        "value": result.value,
+    })
+}
+
+/// Recursively rebuild object keys in sorted order, so output is stable
+/// regardless of the underlying map's insertion-order behavior
+fn sort_json_keys(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut keys: Vec<String> = map.keys().cloned().collect();
+            keys.sort();
+            let mut sorted = serde_json::Map::new();
+            for key in keys {
+                let v = map[&key].clone();
+                sorted.insert(key, sort_json_keys(v));
+            }
+            serde_json::Value::Object(sorted)
+        }
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.into_iter().map(sort_json_keys).collect())
+        }
+        other => other,
+    }
+}
+
+/// Drop any result pair the `compareValues` callback reports as equal
+fn filter_with_compare_values(
+    env: &Env,
+    results: Vec<JsDiffResult>,
+    callback: &JsFunction,
+) -> Result<Vec<JsDiffResult>> {
+    let mut kept = Vec::with_capacity(results.len());
+    for result in results {
+        let (old_value, new_value) = leaf_values(&result);
+        let equal = call_compare_values(env, callback, &result.path, old_value, new_value)?;
+        if !equal {
+            kept.push(result);
+        }
+    }
+    Ok(kept)
+}
+
+/// Extract the `(old, new)` pair a `compareValues` callback should see for a result
+fn leaf_values(result: &JsDiffResult) -> (serde_json::Value, serde_json::Value) {
+    match result.diff_type.as_str() {
+        "Added" => (
+            serde_json::Value::Null,
+            result.new_value.clone().unwrap_or(serde_json::Value::Null),
+        ),
+        "Removed" => (
+            result.value.clone().unwrap_or(serde_json::Value::Null),
+            serde_json::Value::Null,
+        ),
+        _ => (
+            result.old_value.clone().unwrap_or(serde_json::Value::Null),
+            result.new_value.clone().unwrap_or(serde_json::Value::Null),
+        ),
+    }
+}
+
+/// Resets `IN_COMPARE_VALUES` to `false` on drop, including on early return via `?`,
+/// so a failed argument conversion can't wedge the reentrancy guard permanently open
+struct ReentrancyGuard;
+
+impl Drop for ReentrancyGuard {
+    fn drop(&mut self) {
+        IN_COMPARE_VALUES.with(|guard| guard.set(false));
+    }
+}
+
+/// Invoke `compareValues` directly on the calling (JS) thread and read its boolean
+/// return value
+///
+/// `diff`/`createPatch` already run synchronously on the JS thread, so the
+/// callback is simply called in place — no thread hop, and nothing to block on.
+/// A `JsFunction` can only ever be called from the JS thread.
+fn call_compare_values(
+    env: &Env,
+    callback: &JsFunction,
+    path: &str,
+    old_value: serde_json::Value,
+    new_value: serde_json::Value,
+) -> Result<bool> {
+    if IN_COMPARE_VALUES.with(|guard| guard.replace(true)) {
+        return Err(Error::new(
+            Status::GenericFailure,
+            "compareValues callback must not call back into diff()/createPatch()/diffBatch()",
+        ));
+    }
+    let _guard = ReentrancyGuard;
+
+    let path_js = env.create_string(path)?.into_unknown();
+    let old_js = env.to_js_value(&old_value)?;
+    let new_js = env.to_js_value(&new_value)?;
+    callback
+        .call(None, &[path_js, old_js, new_js])
+        .and_then(|value| value.coerce_to_bool()?.get_value())
+}
+
 fn build_diff_options(js_options: JsDiffOptions) -> Result<DiffOptions> {
     let mut options = DiffOptions::default();
 
@@ -356,3 +1208,152 @@ fn convert_js_diff_result(js_result: JsDiffResult) -> Result<DiffResult> {
         )),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn removed(path: &str, value: serde_json::Value) -> JsDiffResult {
+        JsDiffResult {
+            diff_type: "Removed".to_string(),
+            path: path.to_string(),
+            old_value: None,
+            new_value: None,
+            value: Some(value),
+        }
+    }
+
+    fn added(path: &str, new_value: serde_json::Value) -> JsDiffResult {
+        JsDiffResult {
+            diff_type: "Added".to_string(),
+            path: path.to_string(),
+            old_value: None,
+            new_value: Some(new_value),
+            value: None,
+        }
+    }
+
+    #[test]
+    fn apply_patch_rebases_multiple_removals_in_one_array() {
+        // old=[a,b,c,d,e] -> new=[a,b], per the shrink-by-3 case reported against apply_patch
+        let old = serde_json::json!(["a", "b", "c", "d", "e"]);
+        let patch = vec![
+            removed("[2]", serde_json::json!("c")),
+            removed("[3]", serde_json::json!("d")),
+            removed("[4]", serde_json::json!("e")),
+        ];
+
+        let result = apply_patch(old, patch, None).unwrap();
+        assert_eq!(result, serde_json::json!(["a", "b"]));
+    }
+
+    #[test]
+    fn apply_patch_rebases_multiple_additions_in_one_array() {
+        // old=[a,b] -> new=[a,b,c,d,e], the growth counterpart of the shrink case above
+        let old = serde_json::json!(["a", "b"]);
+        let patch = vec![
+            added("[2]", serde_json::json!("c")),
+            added("[3]", serde_json::json!("d")),
+            added("[4]", serde_json::json!("e")),
+        ];
+
+        let result = apply_patch(old, patch, None).unwrap();
+        assert_eq!(result, serde_json::json!(["a", "b", "c", "d", "e"]));
+    }
+
+    #[test]
+    fn apply_patch_tracks_offsets_per_array_independently() {
+        // Shrinking "xs" must not disturb the unrelated offset bookkeeping for "ys"
+        let old = serde_json::json!({
+            "xs": ["a", "b", "c", "d"],
+            "ys": ["p", "q"],
+        });
+        let patch = vec![
+            removed("xs[1]", serde_json::json!("b")),
+            removed("xs[2]", serde_json::json!("c")),
+            added("ys[2]", serde_json::json!("r")),
+            added("ys[3]", serde_json::json!("s")),
+        ];
+
+        let result = apply_patch(old, patch, None).unwrap();
+        assert_eq!(
+            result,
+            serde_json::json!({
+                "xs": ["a", "d"],
+                "ys": ["p", "q", "r", "s"],
+            })
+        );
+    }
+
+    #[test]
+    fn apply_patch_rejects_stale_removal_without_fuzz_factor() {
+        // A Removed entry whose recorded `value` no longer matches what's actually at
+        // that (rebased) index means the patch was computed against different data
+        let old = serde_json::json!(["a", "b", "c"]);
+        let patch = vec![removed("[1]", serde_json::json!("not-b"))];
+
+        assert!(apply_patch(old, patch, None).is_err());
+    }
+
+    #[test]
+    fn rebase_segments_applies_accumulated_offset_only_to_matching_prefix() {
+        let mut offsets = std::collections::HashMap::new();
+        offsets.insert("".to_string(), -1i64);
+        offsets.insert(".ys".to_string(), 2i64);
+
+        let top_level = vec![PathSegment::Index(3)];
+        assert_eq!(
+            rebase_segments(&top_level, &offsets),
+            vec![PathSegment::Index(2)]
+        );
+
+        let nested = vec![PathSegment::Key("ys".to_string()), PathSegment::Index(0)];
+        assert_eq!(
+            rebase_segments(&nested, &offsets),
+            vec![PathSegment::Key("ys".to_string()), PathSegment::Index(2)]
+        );
+    }
+
+    #[test]
+    fn rebase_segments_never_produces_a_negative_index() {
+        let mut offsets = std::collections::HashMap::new();
+        offsets.insert("".to_string(), -5i64);
+
+        let segments = vec![PathSegment::Index(1)];
+        assert_eq!(
+            rebase_segments(&segments, &offsets),
+            vec![PathSegment::Index(0)]
+        );
+    }
+
+    #[test]
+    fn strip_jsonc_removes_comments_and_trailing_commas() {
+        let input = r#"{
+            // leading line comment
+            "a": 1, /* inline block comment */ "b": 2,
+            "c": [1, 2, 3,],
+        }"#;
+        let stripped = strip_jsonc(input);
+        let value: serde_json::Value = serde_json::from_str(&stripped).unwrap();
+        assert_eq!(value, serde_json::json!({"a": 1, "b": 2, "c": [1, 2, 3]}));
+    }
+
+    #[test]
+    fn strip_jsonc_leaves_comment_and_comma_lookalikes_inside_strings_untouched() {
+        let input = r#"{"url": "http://example.com/*x*/", "note": "trailing, comma, inside a string"}"#;
+        let stripped = strip_jsonc(input);
+        let value: serde_json::Value = serde_json::from_str(&stripped).unwrap();
+        assert_eq!(value["url"], "http://example.com/*x*/");
+        assert_eq!(value["note"], "trailing, comma, inside a string");
+    }
+
+    #[test]
+    fn strip_jsonc_handles_escaped_quotes_before_a_comment_marker() {
+        // The escaped quote must not be mistaken for the string's closing quote, which
+        // would otherwise drop the state machine into Normal right before the `//`
+        let input = r#"{"quote": "she said \"// not a comment\""}"#;
+        let stripped = strip_jsonc(input);
+        let value: serde_json::Value = serde_json::from_str(&stripped).unwrap();
+        assert_eq!(value["quote"], "she said \"// not a comment\"");
+    }
+}